@@ -11,12 +11,16 @@
 //! [ref]: https://developer.apple.com/library/mac/documentation/Darwin/Reference/FSEvents_Ref/
 #![allow(clippy::borrow_interior_mutable_const, clippy::cast_possible_wrap)]
 
+use std::collections::{HashMap, VecDeque};
 use std::ffi::c_void;
+use std::future::Future;
 use std::io;
 use std::panic::catch_unwind;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::thread;
 use std::time::Duration;
@@ -30,6 +34,7 @@ use core_foundation::string::CFString;
 use futures::stream::{abortable, AbortHandle, Abortable};
 use futures::{Stream, StreamExt};
 use log::{debug, error};
+use tokio::time::{sleep_until, Instant, Sleep};
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::flags::StreamFlags;
@@ -50,31 +55,137 @@ use crate::raw::{
 /// memory leaks.
 pub struct RawEventStreamHandler {
     runloop: Option<(CFRunLoop, thread::JoinHandle<()>, AbortHandle)>,
+    /// The currently scheduled stream, owned here so its paths can be swapped
+    /// at runtime and so it is torn down on [`abort`](Self::abort).
+    stream: Option<FSEventStream>,
+    event_handler: tokio::sync::mpsc::Sender<RawEvent>,
+    last_event_id: Arc<AtomicU64>,
+    latency: Duration,
+    create_flags: FSEventStreamCreateFlags,
 }
 
 impl RawEventStreamHandler {
     /// Stop a `RawEventStream` and terminate its backing `RunLoop`.
     pub fn abort(&mut self) {
         if let Some((runloop, thread_handle, abort_handle)) = self.runloop.take() {
-            let (tx, rx) = channel();
-            let observer = create_oneshot_observer(kCFRunLoopBeforeWaiting, tx);
-            runloop.add_observer(&observer, unsafe { kCFRunLoopDefaultMode });
-
-            if !runloop.is_waiting() {
-                // Wait the RunLoop to enter Waiting state.
-                rx.recv().expect("channel to receive BeforeWaiting signal");
-            }
-
-            runloop.remove_observer(&observer, unsafe { kCFRunLoopDefaultMode });
+            Self::wait_until_waiting(&runloop);
             runloop.stop();
 
             // Wait for the thread to shut down.
             thread_handle.join().expect("thread to shut down");
 
+            // Tear down the stream now that its run loop has stopped.
+            if let Some(mut stream) = self.stream.take() {
+                stream.stop();
+                stream.invalidate();
+            }
+
             // Abort the stream.
             abort_handle.abort();
         }
     }
+
+    /// Atomically swap the set of watched paths without tearing down the
+    /// channel.
+    ///
+    /// FSEvents does not allow mutating an existing stream's paths, so this
+    /// builds a fresh `FSEventStream` — resuming from the last observed
+    /// [`FSEventStreamEventId`] for continuity — schedules and starts it on the
+    /// *same* worker `RunLoop`, then stops and invalidates the old stream. The
+    /// underlying channel and `RawEventStream` stay alive throughout, so
+    /// consumers see an uninterrupted event flow across the change.
+    ///
+    /// # Errors
+    /// Return error when there's any invalid path in `paths_to_watch`.
+    pub fn set_paths<P: AsRef<Path>>(
+        &mut self,
+        paths_to_watch: impl IntoIterator<Item = P>,
+    ) -> io::Result<()> {
+        let runloop = match &self.runloop {
+            Some((runloop, ..)) => runloop.clone(),
+            // The stream has already been aborted; nothing to reconfigure.
+            None => return Ok(()),
+        };
+
+        let context = StreamContextInfo {
+            event_handler: self.event_handler.clone(),
+            last_event_id: Arc::clone(&self.last_event_id),
+        };
+        // A stored id of `0` means no event has been observed yet; FSEvents
+        // reads `sinceWhen == 0` as "replay from the start of recorded history"
+        // rather than "since now", so fall back to the current event id to
+        // avoid flooding the consumer with the new paths' backlog.
+        let since_when = match self.last_event_id.load(Ordering::SeqCst) {
+            0 => current_event_id(),
+            id => id,
+        };
+
+        let stream_context = FSEventStreamContext::new(context, release_context);
+        let mut new_stream = FSEventStream::new(
+            callback,
+            &stream_context,
+            paths_to_watch,
+            since_when,
+            self.latency,
+            self.create_flags,
+        )?;
+
+        // Bring the new stream online on the same worker run loop *before*
+        // tearing down the old one so there is no gap in the event flow.
+        Self::wait_until_waiting(&runloop);
+        new_stream.schedule(&runloop, unsafe { kCFRunLoopDefaultMode });
+        new_stream.start();
+        runloop.wake_up();
+
+        if let Some(mut old) = self.stream.replace(new_stream) {
+            old.stop();
+            old.invalidate();
+        }
+
+        Ok(())
+    }
+
+    /// Synchronously flush pending events to the channel.
+    ///
+    /// The `latency` passed to [`raw_event_stream`] buffers events before the
+    /// callback fires; this forces the backing `RunLoop` to drain and dispatch
+    /// everything queued so far, blocking until those events have been sent.
+    /// Does nothing once the stream has been [`abort`](Self::abort)ed.
+    pub fn flush_sync(&self) {
+        if let (Some((runloop, ..)), Some(stream)) = (&self.runloop, &self.stream) {
+            Self::wait_until_waiting(runloop);
+            unsafe { fs::FSEventStreamFlushSync(stream.0) };
+        }
+    }
+
+    /// Asynchronously request a flush of pending events.
+    ///
+    /// Unlike [`flush_sync`](Self::flush_sync) this returns immediately with the
+    /// [`FSEventStreamEventId`] up to which delivery was requested, or `0` once
+    /// the stream has been [`abort`](Self::abort)ed.
+    pub fn flush_async(&self) -> FSEventStreamEventId {
+        if let (Some((runloop, ..)), Some(stream)) = (&self.runloop, &self.stream) {
+            Self::wait_until_waiting(runloop);
+            unsafe { fs::FSEventStreamFlushAsync(stream.0) }
+        } else {
+            0
+        }
+    }
+
+    /// Block until `runloop` has entered its waiting state, so that it is live
+    /// and able to service a subsequent `stop`/flush request.
+    fn wait_until_waiting(runloop: &CFRunLoop) {
+        let (tx, rx) = channel();
+        let observer = create_oneshot_observer(kCFRunLoopBeforeWaiting, tx);
+        runloop.add_observer(&observer, unsafe { kCFRunLoopDefaultMode });
+
+        if !runloop.is_waiting() {
+            // Wait the RunLoop to enter Waiting state.
+            rx.recv().expect("channel to receive BeforeWaiting signal");
+        }
+
+        runloop.remove_observer(&observer, unsafe { kCFRunLoopDefaultMode });
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -86,8 +197,96 @@ pub struct RawEvent {
     pub id: FSEventStreamEventId,
 }
 
+impl RawEvent {
+    /// Build a pathless marker event carrying only `flags` and `id`.
+    ///
+    /// FSEvents delivers some sentinel events (for example the one flagged
+    /// `kFSEventStreamEventFlagHistoryDone`) with no meaningful path, so the
+    /// path and inode fields are left empty for these.
+    fn marker(
+        flags: StreamFlags,
+        raw_flags: FSEventStreamEventFlags,
+        id: FSEventStreamEventId,
+    ) -> Self {
+        Self {
+            path: PathBuf::new(),
+            inode: 0,
+            flags,
+            raw_flags,
+            id,
+        }
+    }
+
+    /// Build a `RootChanged` marker that preserves the stale root `path`.
+    ///
+    /// Unlike [`marker`](Self::marker), a `RootChanged` event carries the path
+    /// of the watched root that moved, so callers watching several roots can
+    /// tell which one to re-resolve. The inode is not meaningful here.
+    fn root_changed(
+        path: PathBuf,
+        flags: StreamFlags,
+        raw_flags: FSEventStreamEventFlags,
+        id: FSEventStreamEventId,
+    ) -> Self {
+        Self {
+            path,
+            inode: 0,
+            flags,
+            raw_flags,
+            id,
+        }
+    }
+
+    /// Whether this is the `HistoryDone` sentinel signalling that all replayed
+    /// historical events (requested via `since_when`) have been delivered and
+    /// subsequent events are live.
+    #[must_use]
+    pub fn is_history_done(&self) -> bool {
+        self.flags.contains(StreamFlags::HISTORY_DONE)
+    }
+
+    /// Whether this is the `RootChanged` sentinel signalling that a watched
+    /// root was renamed, moved, or deleted.
+    ///
+    /// Only delivered when the stream was created with the `WatchRoot` flag.
+    /// The event's original path now refers to the stale root rather than a
+    /// child, so long-lived watchers should treat this as a cue to re-resolve
+    /// the root and rebuild the stream against its new location.
+    #[must_use]
+    pub fn is_root_changed(&self) -> bool {
+        self.flags.contains(StreamFlags::ROOT_CHANGED)
+    }
+}
+
+/// Return the most recent [`FSEventStreamEventId`] the system has assigned.
+///
+/// Persist this value (for example on a clean shutdown) and pass it back as
+/// `since_when` to [`raw_event_stream`] on restart to replay the changes that
+/// happened while the watcher was not running. The replay ends with a
+/// `HistoryDone` marker event (see [`RawEvent::is_history_done`]).
+#[must_use]
+pub fn current_event_id() -> FSEventStreamEventId {
+    unsafe { fs::FSEventsGetCurrentEventId() }
+}
+
 pub struct RawEventStream {
     stream: Abortable<ReceiverStream<RawEvent>>,
+    last_event_id: Arc<AtomicU64>,
+}
+
+impl RawEventStream {
+    /// The id of the most recent path event received from FSEvents, or `0` if
+    /// none has been received yet. The `HistoryDone` and `RootChanged` marker
+    /// events do not advance it.
+    ///
+    /// Checkpoint this value so it can be replayed via `since_when` after a
+    /// restart. Note that the special inputs `kFSEventsMonitorGranularityEvent`
+    /// and `kFSEventStreamEventIdSinceNow` remain valid sentinel values for
+    /// `since_when` and are never returned here.
+    #[must_use]
+    pub fn last_event_id(&self) -> FSEventStreamEventId {
+        self.last_event_id.load(Ordering::SeqCst)
+    }
 }
 
 impl Stream for RawEventStream {
@@ -98,8 +297,276 @@ impl Stream for RawEventStream {
     }
 }
 
+impl RawEventStream {
+    /// Adapt this raw stream into a semantic [`EventStream`].
+    ///
+    /// The returned stream yields one [`Event`] per underlying [`RawEvent`],
+    /// translating the coalesced [`StreamFlags`] bitset into a set of
+    /// platform-neutral [`Op`]s so downstream code never has to reason about
+    /// FSEvents' flags directly.
+    pub fn events(self) -> EventStream {
+        EventStream { stream: self }
+    }
+
+    /// Coalesce bursts of events on the same path within a sliding `window`.
+    ///
+    /// Rapid writes to the same file produce many near-duplicate [`RawEvent`]s.
+    /// The returned stream buffers incoming events keyed by path and, once a
+    /// path has been quiet for `window`, emits a single merged event whose
+    /// `flags`/`raw_flags` are the bitwise union of the coalesced
+    /// [`StreamFlags`] and whose `id` is the most recent
+    /// [`FSEventStreamEventId`]. A `Remove` following a `Create` within the
+    /// window therefore surfaces both bits, so consumers can still detect
+    /// transient files. When the underlying stream terminates, every pending
+    /// entry is flushed before the debounced stream ends.
+    pub fn debounced(self, window: Duration) -> Debounced {
+        Debounced {
+            inner: self,
+            window,
+            pending: HashMap::new(),
+            ready: VecDeque::new(),
+            timer: None,
+            done: false,
+        }
+    }
+}
+
+struct Pending {
+    inode: i64,
+    flags: StreamFlags,
+    raw_flags: FSEventStreamEventFlags,
+    id: FSEventStreamEventId,
+    deadline: Instant,
+}
+
+impl Pending {
+    fn into_event(self, path: PathBuf) -> RawEvent {
+        RawEvent {
+            path,
+            inode: self.inode,
+            flags: self.flags,
+            raw_flags: self.raw_flags,
+            id: self.id,
+        }
+    }
+}
+
+/// A debouncing adapter over [`RawEventStream`], created by
+/// [`RawEventStream::debounced`].
+pub struct Debounced {
+    inner: RawEventStream,
+    window: Duration,
+    pending: HashMap<PathBuf, Pending>,
+    ready: VecDeque<RawEvent>,
+    timer: Option<Pin<Box<Sleep>>>,
+    done: bool,
+}
+
+impl Debounced {
+    fn merge(&mut self, event: RawEvent) {
+        let deadline = Instant::now() + self.window;
+        match self.pending.get_mut(&event.path) {
+            Some(pending) => {
+                pending.flags |= event.flags;
+                pending.raw_flags |= event.raw_flags;
+                pending.inode = event.inode;
+                pending.id = event.id;
+                pending.deadline = deadline;
+            }
+            None => {
+                self.pending.insert(
+                    event.path,
+                    Pending {
+                        inode: event.inode,
+                        flags: event.flags,
+                        raw_flags: event.raw_flags,
+                        id: event.id,
+                        deadline,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl Stream for Debounced {
+    type Item = RawEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+
+        // Drain everything currently available from the underlying stream,
+        // accumulating per-path state and refreshing each path's deadline.
+        if !this.done {
+            loop {
+                match this.inner.poll_next_unpin(cx) {
+                    Poll::Ready(Some(event)) => this.merge(event),
+                    Poll::Ready(None) => {
+                        this.done = true;
+                        break;
+                    }
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        loop {
+            if let Some(event) = this.ready.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+
+            if this.done {
+                // Flush all remaining entries regardless of their deadline.
+                let drained: Vec<_> = this.pending.drain().collect();
+                for (path, pending) in drained {
+                    this.ready.push_back(pending.into_event(path));
+                }
+                if this.ready.is_empty() {
+                    return Poll::Ready(None);
+                }
+                continue;
+            }
+
+            let now = Instant::now();
+            let expired: Vec<PathBuf> = this
+                .pending
+                .iter()
+                .filter(|(_, pending)| pending.deadline <= now)
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in expired {
+                if let Some(pending) = this.pending.remove(&path) {
+                    this.ready.push_back(pending.into_event(path));
+                }
+            }
+            if !this.ready.is_empty() {
+                continue;
+            }
+
+            let Some(earliest) = this.pending.values().map(|pending| pending.deadline).min() else {
+                return Poll::Pending;
+            };
+            match &mut this.timer {
+                Some(timer) => timer.as_mut().reset(earliest),
+                None => this.timer = Some(Box::pin(sleep_until(earliest))),
+            }
+            if this
+                .timer
+                .as_mut()
+                .expect("timer just armed")
+                .as_mut()
+                .poll(cx)
+                .is_pending()
+            {
+                return Poll::Pending;
+            }
+            // The timer elapsed; loop again to collect the now-expired entries.
+        }
+    }
+}
+
+/// A platform-neutral file-system operation, decoded from the [`StreamFlags`]
+/// carried by a [`RawEvent`].
+///
+/// The mapping mirrors the one used by [`notify`]: `ITEM_XATTR_MOD` and
+/// `ITEM_INODE_META_MOD` become [`Op::Chmod`], `ITEM_CREATED` becomes
+/// [`Op::Create`], `ITEM_REMOVED` becomes [`Op::Remove`], `ITEM_RENAMED`
+/// becomes [`Op::Rename`], and `ITEM_MODIFIED` becomes [`Op::Write`]. The
+/// sentinel markers also surface, as [`Op::HistoryDone`] and
+/// [`Op::RootChanged`], so the semantic stream never silently drops them.
+///
+/// [`notify`]: https://docs.rs/notify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// A file or directory was created.
+    Create,
+    /// A file or directory was removed.
+    Remove,
+    /// A file or directory was renamed.
+    Rename,
+    /// A file's contents were modified.
+    Write,
+    /// A file's metadata (permissions or extended attributes) changed.
+    Chmod,
+    /// Replay of historical events has finished; see
+    /// [`RawEvent::is_history_done`]. The event carries no path.
+    HistoryDone,
+    /// A watched root was renamed, moved, or deleted; see
+    /// [`RawEvent::is_root_changed`]. The event's path is the stale root.
+    RootChanged,
+}
+
+/// A semantic file-system event carrying the set of [`Op`]s decoded from a
+/// single [`RawEvent`].
+///
+/// Because FSEvents coalesces several changes to the same path into one
+/// callback event, `ops` may contain more than one operation — for example a
+/// file created and then written within the same latency window surfaces both
+/// [`Op::Create`] and [`Op::Write`].
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub path: PathBuf,
+    pub inode: i64,
+    pub ops: Vec<Op>,
+    pub id: FSEventStreamEventId,
+}
+
+impl Event {
+    fn from_raw(raw: RawEvent) -> Self {
+        let mut ops = Vec::new();
+        if raw
+            .flags
+            .intersects(StreamFlags::ITEM_XATTR_MOD | StreamFlags::ITEM_INODE_META_MOD)
+        {
+            ops.push(Op::Chmod);
+        }
+        if raw.flags.contains(StreamFlags::ITEM_CREATED) {
+            ops.push(Op::Create);
+        }
+        if raw.flags.contains(StreamFlags::ITEM_REMOVED) {
+            ops.push(Op::Remove);
+        }
+        if raw.flags.contains(StreamFlags::ITEM_RENAMED) {
+            ops.push(Op::Rename);
+        }
+        if raw.flags.contains(StreamFlags::ITEM_MODIFIED) {
+            ops.push(Op::Write);
+        }
+        if raw.flags.contains(StreamFlags::HISTORY_DONE) {
+            ops.push(Op::HistoryDone);
+        }
+        if raw.flags.contains(StreamFlags::ROOT_CHANGED) {
+            ops.push(Op::RootChanged);
+        }
+        Self {
+            path: raw.path,
+            inode: raw.inode,
+            ops,
+            id: raw.id,
+        }
+    }
+}
+
+/// A semantic wrapper over [`RawEventStream`] that yields [`Event`]s.
+///
+/// Construct one with [`RawEventStream::events`].
+pub struct EventStream {
+    stream: RawEventStream,
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream
+            .poll_next_unpin(cx)
+            .map(|opt| opt.map(Event::from_raw))
+    }
+}
+
 struct StreamContextInfo {
     event_handler: tokio::sync::mpsc::Sender<RawEvent>,
+    last_event_id: Arc<AtomicU64>,
 }
 
 impl_release_callback!(release_context, StreamContextInfo);
@@ -116,6 +583,10 @@ impl<T> SendWrapper<T> {
 
 /// Create a new `RawEventStream` and `RawEventStreamHandler` pair.
 ///
+/// Pass `kFSEventStreamCreateFlagWatchRoot` in `flags` to be notified when a
+/// watched root is renamed, moved, or deleted; such changes surface as a
+/// `RootChanged` marker event (see [`RawEvent::is_root_changed`]).
+///
 /// # Errors
 /// Return error when there's any invalid path in `paths_to_watch`.
 pub fn raw_event_stream<P: AsRef<Path>>(
@@ -130,25 +601,35 @@ pub fn raw_event_stream<P: AsRef<Path>>(
     // to the rest of the system. This will be owned by the stream, and will be freed when the
     // stream is closed. This means we will leak the context if we panic before reacing
     // `FSEventStreamRelease`.
+    let last_event_id = Arc::new(AtomicU64::new(0));
     let context = StreamContextInfo {
-        event_handler: event_tx,
+        event_handler: event_tx.clone(),
+        last_event_id: Arc::clone(&last_event_id),
     };
 
     let stream_context = FSEventStreamContext::new(context, release_context);
 
     // We must append some additional flags because our callback parse them so
+    let create_flags =
+        flags | kFSEventStreamCreateFlagUseCFTypes | kFSEventStreamCreateFlagUseExtendedData;
     let mut stream = FSEventStream::new(
         callback,
         &stream_context,
         paths_to_watch,
         since_when,
         latency,
-        flags | kFSEventStreamCreateFlagUseCFTypes | kFSEventStreamCreateFlagUseExtendedData,
+        create_flags,
     )?;
 
     // channel to pass runloop around
     let (runloop_tx, runloop_rx) = channel();
 
+    // The stream must be scheduled and started *inside* the worker before
+    // `run_current`, otherwise the run loop's mode has no input source and
+    // `CFRunLoopRun` returns immediately. Once scheduled, the source keeps the
+    // run loop alive, so we hand ownership of the stream back to the handler —
+    // that lets `set_paths` swap it at runtime (see
+    // `RawEventStreamHandler::set_paths`).
     let thread_handle = thread::spawn(move || {
         let current_runloop = CFRunLoop::get_current();
 
@@ -158,23 +639,27 @@ pub fn raw_event_stream<P: AsRef<Path>>(
         // the calling to CFRunLoopRun will be terminated by CFRunLoopStop call in drop()
         // SAFETY: `CF_REF` is thread-safe.
         runloop_tx
-            .send(unsafe { SendWrapper::new(current_runloop) })
+            .send(unsafe { SendWrapper::new((current_runloop, stream)) })
             .expect("send runloop to stream");
 
         CFRunLoop::run_current();
-        stream.stop();
-        stream.invalidate();
     });
 
-    let (stream, stream_handle) = abortable(ReceiverStream::new(event_rx));
+    let (runloop, stream) = runloop_rx.recv().expect("receive runloop from worker").0;
+
+    let (stream_rx, stream_handle) = abortable(ReceiverStream::new(event_rx));
     Ok((
-        RawEventStream { stream },
+        RawEventStream {
+            stream: stream_rx,
+            last_event_id: Arc::clone(&last_event_id),
+        },
         RawEventStreamHandler {
-            runloop: Some((
-                runloop_rx.recv().expect("receive runloop from worker").0,
-                thread_handle,
-                stream_handle,
-            )),
+            runloop: Some((runloop, thread_handle, stream_handle)),
+            stream: Some(stream),
+            event_handler: event_tx,
+            last_event_id,
+            latency,
+            create_flags,
         },
     ))
 }
@@ -217,13 +702,47 @@ fn callback_impl(
     let event_paths = unsafe { CFArray::<CFDictionary<CFString>>::from_void(event_paths) };
     let info = info as *const StreamContextInfo;
     let event_handler = unsafe { &(*info).event_handler };
+    let last_event_id = unsafe { &(*info).last_event_id };
 
     for idx in 0..num_events {
-        match Ok((
-            unsafe { event_paths.get_unchecked(idx as CFIndex) },
-            unsafe { *event_flags.add(idx) },
-            unsafe { *event_ids.add(idx) },
-        ))
+        let raw_flags = unsafe { *event_flags.add(idx) };
+        let id = unsafe { *event_ids.add(idx) };
+
+        // Sentinel events must be recognized before we try to read the extended
+        // path/inode data, which they don't fully carry (`HistoryDone` has no
+        // path, `RootChanged` no meaningful inode). Use `from_bits_truncate`
+        // rather than the strict `from_bits` so that any unmodeled bits FSEvents
+        // ORs in can't make us miss a marker.
+        let flags = StreamFlags::from_bits_truncate(raw_flags);
+        // A sentinel's id is not a monotonic data cursor, so it is not
+        // checkpointed into `last_event_id`.
+        if flags.contains(StreamFlags::HISTORY_DONE) {
+            // `HistoryDone` carries no path.
+            if let Err(e) = event_handler.try_send(RawEvent::marker(flags, raw_flags, id)) {
+                error!("Unable to raw event from low-level callback: {}", e);
+            }
+            continue;
+        }
+        if flags.contains(StreamFlags::ROOT_CHANGED) {
+            // `RootChanged`'s path is the (now stale) original root; surface it
+            // so callers watching several roots know which one to re-resolve.
+            // Only the path key is read — the inode is not meaningful here.
+            let extended = unsafe { event_paths.get_unchecked(idx as CFIndex) };
+            let path = unsafe {
+                CFString::from_void(*extended.get(&*kFSEventStreamEventExtendedDataPathKey))
+            };
+            let event =
+                RawEvent::root_changed(PathBuf::from((*path).to_string()), flags, raw_flags, id);
+            if let Err(e) = event_handler.try_send(event) {
+                error!("Unable to raw event from low-level callback: {}", e);
+            }
+            continue;
+        }
+
+        // Only genuine path events advance the durable-resume cursor.
+        last_event_id.store(id, Ordering::SeqCst);
+
+        match Ok((unsafe { event_paths.get_unchecked(idx as CFIndex) }, raw_flags, id))
         .and_then(|(extended, raw_flags, id)| {
             let path = unsafe {
                 CFString::from_void(*extended.get(&*kFSEventStreamEventExtendedDataPathKey))
@@ -264,11 +783,138 @@ fn callback_impl(
 
 #[cfg(test)]
 mod test {
-    use crate::fsevent::StreamContextInfo;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::stream::abortable;
+    use futures::StreamExt;
+    use tokio_stream::wrappers::ReceiverStream;
+
+    use crate::flags::StreamFlags;
+    use crate::fsevent::{Debounced, Event, Op, RawEvent, RawEventStream, StreamContextInfo};
+
+    fn raw_event(path: &str, flags: StreamFlags, id: u64) -> RawEvent {
+        RawEvent {
+            path: PathBuf::from(path),
+            inode: 1,
+            flags,
+            raw_flags: flags.bits(),
+            id,
+        }
+    }
 
     #[test]
     fn test_steam_context_info_send_and_sync() {
         fn check_send<T: Send + Sync>() {}
         check_send::<StreamContextInfo>();
     }
+
+    #[test]
+    fn event_maps_single_op() {
+        let event = Event::from_raw(raw_event("/a", StreamFlags::ITEM_CREATED, 1));
+        assert_eq!(event.ops, vec![Op::Create]);
+        assert_eq!(event.path, PathBuf::from("/a"));
+    }
+
+    #[test]
+    fn event_maps_metadata_flags_to_chmod() {
+        let event = Event::from_raw(raw_event("/a", StreamFlags::ITEM_INODE_META_MOD, 1));
+        assert_eq!(event.ops, vec![Op::Chmod]);
+    }
+
+    #[test]
+    fn event_coalesces_multiple_ops() {
+        let flags = StreamFlags::ITEM_CREATED | StreamFlags::ITEM_MODIFIED;
+        let event = Event::from_raw(raw_event("/a", flags, 1));
+        assert_eq!(event.ops, vec![Op::Create, Op::Write]);
+    }
+
+    #[test]
+    fn event_surfaces_markers() {
+        let history = Event::from_raw(raw_event("", StreamFlags::HISTORY_DONE, 1));
+        assert_eq!(history.ops, vec![Op::HistoryDone]);
+        let root = Event::from_raw(raw_event("", StreamFlags::ROOT_CHANGED, 1));
+        assert_eq!(root.ops, vec![Op::RootChanged]);
+    }
+
+    fn debounced_channel(
+        window: Duration,
+    ) -> (tokio::sync::mpsc::Sender<RawEvent>, Debounced) {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let (stream, _handle) = abortable(ReceiverStream::new(rx));
+        let raw = RawEventStream {
+            stream,
+            last_event_id: Arc::new(AtomicU64::new(0)),
+        };
+        (tx, raw.debounced(window))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_merges_same_path() {
+        let (tx, mut deb) = debounced_channel(Duration::from_millis(100));
+        tx.send(raw_event("/a", StreamFlags::ITEM_CREATED, 1))
+            .await
+            .unwrap();
+        tx.send(raw_event("/a", StreamFlags::ITEM_MODIFIED, 2))
+            .await
+            .unwrap();
+        drop(tx);
+
+        let event = deb.next().await.unwrap();
+        assert_eq!(event.path, PathBuf::from("/a"));
+        assert!(event.flags.contains(StreamFlags::ITEM_CREATED));
+        assert!(event.flags.contains(StreamFlags::ITEM_MODIFIED));
+        assert_eq!(event.id, 2);
+        assert!(deb.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_emits_after_window() {
+        let (tx, mut deb) = debounced_channel(Duration::from_millis(100));
+        tx.send(raw_event("/a", StreamFlags::ITEM_MODIFIED, 5))
+            .await
+            .unwrap();
+
+        // With no terminating drop, the entry is emitted only once its deadline
+        // passes; the paused clock auto-advances to the armed timer.
+        let event = deb.next().await.unwrap();
+        assert_eq!(event.id, 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_keeps_create_and_remove() {
+        let (tx, mut deb) = debounced_channel(Duration::from_millis(100));
+        tx.send(raw_event("/tmp/x", StreamFlags::ITEM_CREATED, 1))
+            .await
+            .unwrap();
+        tx.send(raw_event("/tmp/x", StreamFlags::ITEM_REMOVED, 2))
+            .await
+            .unwrap();
+        drop(tx);
+
+        let event = deb.next().await.unwrap();
+        assert!(event.flags.contains(StreamFlags::ITEM_CREATED));
+        assert!(event.flags.contains(StreamFlags::ITEM_REMOVED));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_flushes_pending_on_terminate() {
+        let (tx, mut deb) = debounced_channel(Duration::from_millis(100));
+        tx.send(raw_event("/a", StreamFlags::ITEM_CREATED, 1))
+            .await
+            .unwrap();
+        tx.send(raw_event("/b", StreamFlags::ITEM_CREATED, 2))
+            .await
+            .unwrap();
+        drop(tx);
+
+        let mut paths = vec![];
+        while let Some(event) = deb.next().await {
+            paths.push(event.path);
+        }
+        paths.sort();
+        assert_eq!(paths, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+    }
 }